@@ -24,6 +24,10 @@ const MAX_ADC_CLK_FREQ: Hertz = Hertz::mhz(60);
 #[cfg(stm32h7)]
 const MAX_ADC_CLK_FREQ: Hertz = Hertz::mhz(50);
 
+/// Min single ADC operation clock frequency
+#[cfg(stm32g4)]
+const MIN_ADC_CLK_FREQ: Hertz = Hertz::khz(140);
+
 #[cfg(stm32g4)]
 const VREF_CHANNEL: u8 = 18;
 #[cfg(stm32g4)]
@@ -149,6 +153,14 @@ impl<'d, T: Instance> Adc<'d, T> {
             panic!("Maximal allowed frequency for the ADC is {} MHz and it varies with different packages, refer to ST docs for more information.", MAX_ADC_CLK_FREQ.0 /  1_000_000 );
         }
 
+        #[cfg(stm32g4)]
+        if frequency < MIN_ADC_CLK_FREQ {
+            panic!(
+                "Minimal allowed frequency for the ADC is {} kHz, check the ADC clock mux and the AHB prescaler.",
+                MIN_ADC_CLK_FREQ.0 / 1_000
+            );
+        }
+
         let mut s = Self {
             adc,
             sample_time: SampleTime::from_bits(0),
@@ -28,6 +28,23 @@ macro_rules! rcc_assert {
     };
 }
 
+#[collapse_debuginfo(yes)]
+macro_rules! rcc_ensure {
+    ($cond:expr, $err:expr) => {{
+        #[cfg(not(feature = "unchecked-overclocking"))]
+        if !($cond) {
+            return Err($err);
+        }
+        #[cfg(feature = "unchecked-overclocking")]
+        {
+            #[cfg(feature = "log")]
+            ::log::warn!("`rcc_ensure!` skipped: `unchecked-overclocking` feature is enabled.");
+            #[cfg(feature = "defmt")]
+            ::defmt::warn!("`rcc_ensure!` skipped: `unchecked-overclocking` feature is enabled.");
+        }
+    }};
+}
+
 #[collapse_debuginfo(yes)]
 macro_rules! assert {
     ($($x:tt)*) => {
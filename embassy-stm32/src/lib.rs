@@ -308,8 +308,26 @@ impl Default for Config {
 /// This returns the peripheral singletons that can be used for creating drivers.
 ///
 /// This should only be called once at startup, otherwise it panics.
+///
+/// This also panics if the clock configuration is invalid, use [`try_init`] to handle that case.
 #[cfg(not(feature = "_dual-core"))]
 pub fn init(config: Config) -> Peripherals {
+    unwrap!(try_init(config))
+}
+
+/// Initialize the `embassy-stm32` HAL with the provided configuration.
+///
+/// Same as [`init`], but returns an error instead of panicking if the clock configuration
+/// is invalid.
+///
+/// On error no peripheral singletons are taken and `try_init` can be called again, for example
+/// with a fallback configuration. On G4 an invalid configuration is rejected before the clocks
+/// are touched. If HSE or the PLL fail to start, the one that timed out is turned off again and
+/// the system runs from HSI16.
+///
+/// This should only be called once successfully, otherwise it panics.
+#[cfg(not(feature = "_dual-core"))]
+pub fn try_init(config: Config) -> Result<Peripherals, rcc::RccError> {
     init_hw(config)
 }
 
@@ -368,7 +386,7 @@ mod dual_core {
         shared_data.init_flag.store(0, Ordering::SeqCst);
 
         rcc::set_freqs_ptr(shared_data.clocks.get());
-        let p = init_hw(config);
+        let p = unwrap!(init_hw(config));
 
         unsafe { *shared_data.config.get() }.write(config.into());
 
@@ -418,6 +436,7 @@ mod dual_core {
 
     fn init_secondary_hw(shared_data: &'static SharedData) -> Peripherals {
         rcc::set_freqs_ptr(shared_data.clocks.get());
+        rcc::set_freqs_valid();
 
         let config = unsafe { (*shared_data.config.get()).assume_init() };
 
@@ -481,9 +500,16 @@ mod dual_core {
 #[cfg(feature = "_dual-core")]
 pub use dual_core::*;
 
-fn init_hw(config: Config) -> Peripherals {
+/// Set once `init_hw` succeeded. The singletons are only taken after the clock setup, so this is
+/// checked up front to panic on a second `init` before any hardware is reconfigured.
+static mut INIT_DONE: bool = false;
+
+fn init_hw(config: Config) -> Result<Peripherals, rcc::RccError> {
     critical_section::with(|cs| {
-        let p = Peripherals::take_with_cs(cs);
+        // safety: OK because we're inside a CS.
+        if unsafe { INIT_DONE } {
+            panic!("init called more than once!")
+        }
 
         #[cfg(dbgmcu)]
         crate::pac::DBGMCU.cr().modify(|cr| {
@@ -600,7 +626,11 @@ fn init_hw(config: Config) -> Peripherals {
             #[cfg(feature = "exti")]
             exti::init(cs);
 
-            rcc::init(config.rcc);
+            rcc::try_init(config.rcc)?;
+
+            // Only hand out the singletons once the clocks are up, so a failed init can be retried.
+            INIT_DONE = true;
+            let p = Peripherals::take_with_cs(cs);
 
             // must be after rcc init
             #[cfg(feature = "_time-driver")]
@@ -608,11 +638,13 @@ fn init_hw(config: Config) -> Peripherals {
 
             #[cfg(feature = "low-power")]
             {
+                #[cfg(any(stm32g4, stm32wl))]
+                crate::rcc::set_resume_config(config.rcc);
                 crate::rcc::REFCOUNT_STOP2 = 0;
                 crate::rcc::REFCOUNT_STOP1 = 0;
             }
-        }
 
-        p
+            Ok(p)
+        }
     })
 }
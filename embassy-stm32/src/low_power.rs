@@ -152,6 +152,7 @@ pub struct Executor {
     not_send: PhantomData<*mut ()>,
     scb: SCB,
     time_driver: &'static RtcDriver,
+    stop_requested: bool,
 }
 
 impl Executor {
@@ -165,6 +166,7 @@ impl Executor {
                 not_send: PhantomData,
                 scb: cortex_m::Peripherals::steal().SCB,
                 time_driver: get_driver(),
+                stop_requested: false,
             });
 
             let executor = EXECUTOR.as_mut().unwrap();
@@ -174,6 +176,13 @@ impl Executor {
     }
 
     unsafe fn on_wakeup_irq(&mut self) {
+        // STOP falls back to HSI/MSI with HSE and the PLL off. Restore the clocks before the
+        // timer starts counting again, so embassy-time keeps its tick rate. This handler runs on
+        // every EXTI and timer interrupt, only do it for the first one after requesting STOP.
+        if core::mem::take(&mut self.stop_requested) {
+            #[cfg(any(stm32g4, stm32wl))]
+            crate::rcc::resume_after_stop();
+        }
         self.time_driver.resume_time();
         trace!("low power: resume");
     }
@@ -233,7 +242,10 @@ impl Executor {
         self.configure_stop(stop_mode);
 
         #[cfg(not(feature = "low-power-debug-with-sleep"))]
-        self.scb.set_sleepdeep();
+        {
+            self.scb.set_sleepdeep();
+            self.stop_requested = true;
+        }
     }
 
     /// Run the executor.
@@ -9,6 +9,28 @@ pub const LSI_FREQ: Hertz = Hertz(40_000);
 #[cfg(not(any(stm32f0, stm32f1, stm32f3)))]
 pub const LSI_FREQ: Hertz = Hertz(32_000);
 
+/// Returns `true` if the LSI oscillator is running and stable.
+///
+/// `LsConfig` waits for this when it enables LSI. The IWDG starts LSI on its own when unleashed.
+pub fn lsi_ready() -> bool {
+    #[cfg(any(stm32u5, stm32h5, stm32wba))]
+    let csr = crate::pac::RCC.bdcr();
+    #[cfg(not(any(stm32u5, stm32h5, stm32wba, stm32c0)))]
+    let csr = crate::pac::RCC.csr();
+    #[cfg(any(stm32c0))]
+    let csr = crate::pac::RCC.csr2();
+
+    #[cfg(not(any(rcc_wb, rcc_wba)))]
+    return csr.read().lsirdy();
+    #[cfg(any(rcc_wb, rcc_wba))]
+    return csr.read().lsi1rdy();
+}
+
+/// The RTC clock source currently selected in the backup domain.
+pub(crate) fn rtc_clock_source() -> RtcClockSource {
+    bdcr().read().rtcsel()
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Copy)]
 pub enum LseMode {
@@ -135,13 +157,22 @@ impl Default for LsConfig {
 }
 
 impl LsConfig {
+    #[cfg(not(stm32g4))]
     pub(crate) fn init(&self) -> Option<Hertz> {
+        self.init_with_hse(None)
+    }
+
+    /// Same as `init`, but also allows clocking the RTC from the HSE on chips that support it.
+    pub(crate) fn init_with_hse(&self, _hse: Option<Hertz>) -> Option<Hertz> {
         let rtc_clk = match self.rtc {
             RtcClockSource::LSI => {
                 assert!(self.lsi);
                 Some(LSI_FREQ)
             }
             RtcClockSource::LSE => Some(self.lse.as_ref().unwrap().frequency),
+            // The RTC is clocked from HSE divided by 32.
+            #[cfg(stm32g4)]
+            RtcClockSource::HSE => Some(unwrap!(_hse, "RTC clock source is HSE, but HSE is not enabled") / 32u32),
             RtcClockSource::DISABLE => None,
             _ => todo!(),
         };
@@ -181,11 +212,7 @@ impl LsConfig {
             #[cfg(any(rcc_wb, rcc_wba))]
             csr.modify(|w| w.set_lsi1on(true));
 
-            #[cfg(not(any(rcc_wb, rcc_wba)))]
-            while !csr.read().lsirdy() {}
-
-            #[cfg(any(rcc_wb, rcc_wba))]
-            while !csr.read().lsi1rdy() {}
+            while !lsi_ready() {}
         }
 
         // backup domain configuration (LSEON, RTCEN, RTCSEL) is kept across resets.
@@ -1,4 +1,8 @@
+use core::ops::RangeInclusive;
+
+use super::RccError;
 use crate::pac::flash::vals::Latency;
+pub use crate::pac::pwr::vals::Vos as VoltageRange;
 pub use crate::pac::rcc::vals::{
     Hpre as AHBPrescaler, Pllm as PllPreDiv, Plln as PllMul, Pllp as PllPDiv, Pllq as PllQDiv, Pllr as PllRDiv,
     Pllsrc as PllSource, Ppre as APBPrescaler, Sw as Sysclk,
@@ -53,6 +57,111 @@ pub struct Pll {
     pub divr: Option<PllRDiv>,
 }
 
+impl Pll {
+    /// Check this configuration against the PLL limits of the voltage range for the given source
+    /// clock frequency, and return the resulting output frequencies.
+    ///
+    /// `init` runs the same check. As a `const fn` it can also reject a bad PLL configuration at
+    /// compile time:
+    ///
+    /// ```ignore
+    /// const PLL: Pll = Pll {
+    ///     source: PllSource::HSE,
+    ///     prediv: PllPreDiv::DIV6,
+    ///     mul: PllMul::MUL85,
+    ///     divp: None,
+    ///     divq: None,
+    ///     divr: Some(PllRDiv::DIV2),
+    /// };
+    /// const _: () = assert!(PLL.check(Hertz::mhz(24), VoltageRange::RANGE1).is_ok());
+    /// ```
+    pub const fn check(&self, src_freq: Hertz, range: VoltageRange) -> Result<PllFreq, RccError> {
+        let range2 = matches!(range, VoltageRange::RANGE2);
+
+        let in_freq = src_freq.0 / prediv_factor(self.prediv);
+        if !in_range(Hertz(in_freq), &max::PLL_IN) {
+            return Err(RccError::VcoInputOutOfRange);
+        }
+        let vco_range = if range2 { &max::range2::PLL_VCO } else { &max::PLL_VCO };
+        if !in_range(self.vco(src_freq), vco_range) {
+            return Err(RccError::VcoOutputOutOfRange);
+        }
+
+        let freq = self.output(src_freq);
+        let p_range = if range2 { &max::range2::PLL_P } else { &max::PLL_P };
+        if !opt_in_range(freq.pll_p, p_range) {
+            return Err(RccError::PllPOutOfRange);
+        }
+        let q_range = if range2 { &max::range2::PLL_Q } else { &max::PLL_Q };
+        if !opt_in_range(freq.pll_q, q_range) {
+            return Err(RccError::PllQOutOfRange);
+        }
+        let r_range = if range2 { &max::range2::PLL_R } else { &max::PLL_R };
+        if !opt_in_range(freq.pll_r, r_range) {
+            return Err(RccError::PllROutOfRange);
+        }
+        Ok(freq)
+    }
+
+    /// VCO frequency for the given source clock frequency.
+    const fn vco(&self, src_freq: Hertz) -> Hertz {
+        Hertz(src_freq.0 / prediv_factor(self.prediv) * mul_factor(self.mul))
+    }
+
+    /// Output frequencies for the given source clock frequency, without any range checks.
+    const fn output(&self, src_freq: Hertz) -> PllFreq {
+        let vco = self.vco(src_freq).0;
+        PllFreq {
+            pll_p: match self.divp {
+                Some(div) => Some(Hertz(vco / divp_factor(div))),
+                None => None,
+            },
+            pll_q: match self.divq {
+                Some(div) => Some(Hertz(vco / divq_factor(div))),
+                None => None,
+            },
+            pll_r: match self.divr {
+                Some(div) => Some(Hertz(vco / divr_factor(div))),
+                None => None,
+            },
+        }
+    }
+}
+
+// The `Div`/`Mul` impls for the PLL factors are not `const`, so decode the register encodings here.
+// PLLM is the division factor minus one, PLLN and PLLPDIV are the factor itself, PLLQ and PLLR
+// encode 2, 4, 6 and 8.
+const fn prediv_factor(div: PllPreDiv) -> u32 {
+    div.to_bits() as u32 + 1
+}
+
+const fn mul_factor(mul: PllMul) -> u32 {
+    mul.to_bits() as u32
+}
+
+const fn divp_factor(div: PllPDiv) -> u32 {
+    div.to_bits() as u32
+}
+
+const fn divq_factor(div: PllQDiv) -> u32 {
+    (div.to_bits() as u32 + 1) * 2
+}
+
+const fn divr_factor(div: PllRDiv) -> u32 {
+    (div.to_bits() as u32 + 1) * 2
+}
+
+const fn in_range(freq: Hertz, range: &RangeInclusive<Hertz>) -> bool {
+    freq.0 >= range.start().0 && freq.0 <= range.end().0
+}
+
+const fn opt_in_range(freq: Option<Hertz>, range: &RangeInclusive<Hertz>) -> bool {
+    match freq {
+        Some(freq) => in_range(freq, range),
+        None => true,
+    }
+}
+
 /// Clocks configutation
 #[non_exhaustive]
 #[derive(Clone, Copy)]
@@ -78,15 +187,36 @@ pub struct Config {
     pub apb1_pre: APBPrescaler,
     pub apb2_pre: APBPrescaler,
 
+    /// Enable low-power run mode. HCLK must not exceed 2MHz, see [`Config::low_power_run_hsi16`].
     pub low_power_run: bool,
 
     /// Low-Speed Clock Configuration
     pub ls: super::LsConfig,
 
     /// Enable range1 boost mode
-    /// Recommended when the SYSCLK frequency is greater than 150MHz.
+    /// Required when the SYSCLK frequency is greater than 150MHz.
     pub boost: bool,
 
+    /// Extra time to wait after HSE reports ready, in microseconds.
+    ///
+    /// HSERDY only means the oscillator amplitude crossed a threshold. Some crystals keep drifting
+    /// for a while after that and TCXOs have a specified startup time, which matters when the
+    /// clock is a reference for an RF design.
+    ///
+    /// Values above about 268s are capped.
+    pub hse_settle_us: u32,
+
+    /// Extra time to wait after the PLL reports lock, in microseconds.
+    ///
+    /// PLLRDY is set on lock, but the output phase noise keeps improving for a short while after.
+    ///
+    /// Capped the same way as `hse_settle_us`.
+    pub pll_settle_us: u32,
+
+    /// Voltage scaling range
+    /// Range 2 saves power but limits SYSCLK to 26MHz and does not support boost mode.
+    pub voltage_range: VoltageRange,
+
     /// Per-peripheral kernel clock selection muxes
     pub mux: super::mux::ClockMux,
 }
@@ -106,139 +236,259 @@ impl Default for Config {
             low_power_run: false,
             ls: Default::default(),
             boost: false,
+            hse_settle_us: 0,
+            pll_settle_us: 0,
+            voltage_range: VoltageRange::RANGE1,
             mux: Default::default(),
         }
     }
 }
 
-#[derive(Default)]
+impl Config {
+    /// Low-power run configuration: HSI16 divided by 8 for a 2MHz HCLK, with the PLL and HSI48 off.
+    pub fn low_power_run_hsi16() -> Self {
+        Self {
+            hsi48: None,
+            ahb_pre: AHBPrescaler::DIV8,
+            low_power_run: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Number of polls of HSERDY before giving up. This is well over 100ms while running from HSI16,
+/// crystals normally start within a few milliseconds.
+const HSE_TIMEOUT_ITERATIONS: u32 = 1_000_000;
+
+/// Number of polls of PLLRDY before giving up. The PLL normally locks within 100us.
+const PLL_TIMEOUT_ITERATIONS: u32 = 100_000;
+
+fn wait_ready(iterations: u32, ready: impl Fn() -> bool, err: RccError) -> Result<(), RccError> {
+    for _ in 0..iterations {
+        if ready() {
+            return Ok(());
+        }
+    }
+    Err(err)
+}
+
+/// Busy-wait for `us` microseconds. Only valid during the clock setup, while running from HSI16.
+fn settle(us: u32) {
+    if us > 0 {
+        cortex_m::asm::delay(us.saturating_mul(HSI_FREQ.0 / 1_000_000));
+    }
+}
+
+/// PLL output frequencies.
+#[derive(Clone, Copy, Default)]
 pub struct PllFreq {
     pub pll_p: Option<Hertz>,
     pub pll_q: Option<Hertz>,
     pub pll_r: Option<Hertz>,
 }
 
-pub(crate) unsafe fn init(config: Config) {
-    // Turn on the HSI
-    RCC.cr().modify(|w| w.set_hsion(true));
-    while !RCC.cr().read().hsirdy() {}
-
-    // Use the HSI clock as system clock during the actual clock setup
-    RCC.cfgr().modify(|w| w.set_sw(Sysclk::HSI));
-    while RCC.cfgr().read().sws() != Sysclk::HSI {}
+pub(crate) unsafe fn try_init(config: Config) -> Result<(), RccError> {
+    // Check the whole configuration before the first register write, so an invalid one leaves the
+    // clocks untouched. Only the HSE and PLL start-up can fail after this.
+    if config.boost && config.voltage_range != VoltageRange::RANGE1 {
+        return Err(RccError::BoostUnavailable);
+    }
 
-    // Configure HSI
     let hsi = match config.hsi {
         false => None,
         true => Some(HSI_FREQ),
     };
 
-    // Configure HSE
     let hse = match config.hse {
-        None => {
-            RCC.cr().modify(|w| w.set_hseon(false));
-            None
-        }
+        None => None,
         Some(hse) => {
             match hse.mode {
-                HseMode::Bypass => rcc_assert!(max::HSE_BYP.contains(&hse.freq)),
-                HseMode::Oscillator => rcc_assert!(max::HSE_OSC.contains(&hse.freq)),
+                HseMode::Bypass => rcc_ensure!(max::HSE_BYP.contains(&hse.freq), RccError::HseOutOfRange),
+                HseMode::Oscillator => rcc_ensure!(max::HSE_OSC.contains(&hse.freq), RccError::HseOutOfRange),
             }
-
-            RCC.cr().modify(|w| w.set_hsebyp(hse.mode != HseMode::Oscillator));
-            RCC.cr().modify(|w| w.set_hseon(true));
-            while !RCC.cr().read().hserdy() {}
             Some(hse.freq)
         }
     };
 
-    // Configure HSI48 if required
-    let hsi48 = config.hsi48.map(super::init_hsi48);
-
-    let pll = config
-        .pll
-        .map(|pll_config| {
+    let (pll, pll_src_freq) = match config.pll {
+        None => (PllFreq::default(), None),
+        Some(pll_config) => {
             let src_freq = match pll_config.source {
-                PllSource::HSI => unwrap!(hsi),
-                PllSource::HSE => unwrap!(hse),
+                PllSource::HSI => hsi.ok_or(RccError::ClockNotEnabled)?,
+                PllSource::HSE => hse.ok_or(RccError::ClockNotEnabled)?,
                 _ => unreachable!(),
             };
 
+            #[cfg(not(feature = "unchecked-overclocking"))]
+            let freq = pll_config.check(src_freq, config.voltage_range)?;
+            #[cfg(feature = "unchecked-overclocking")]
+            let freq = pll_config.output(src_freq);
+
+            (freq, Some(src_freq))
+        }
+    };
+
+    let sys = match config.sys {
+        Sysclk::HSI => hsi,
+        Sysclk::HSE => hse,
+        Sysclk::PLL1_R => pll.pll_r,
+        _ => unreachable!(),
+    }
+    .ok_or(RccError::ClockNotEnabled)?;
+
+    let sysclk_range = match config.voltage_range {
+        VoltageRange::RANGE2 => max::range2::SYSCLK,
+        _ => max::SYSCLK,
+    };
+    rcc_ensure!(sysclk_range.contains(&sys), RccError::SysclkOutOfRange);
+    // Range 1 normal mode tops out at 150MHz, anything faster requires boost mode.
+    rcc_ensure!(
+        config.boost || max::SYSCLK_NO_BOOST.contains(&sys),
+        RccError::BoostRequired
+    );
+
+    // Calculate the AHB frequency (HCLK), among other things so we can calculate the correct flash read latency.
+    let hclk = sys / config.ahb_pre;
+    rcc_ensure!(max::HCLK.contains(&hclk), RccError::HclkOutOfRange);
+    if config.low_power_run && hclk > max::HCLK_LOW_POWER_RUN {
+        return Err(RccError::LowPowerRunTooFast);
+    }
+
+    let (pclk1, pclk1_tim) = super::util::calc_pclk(hclk, config.apb1_pre);
+    let (pclk2, pclk2_tim) = super::util::calc_pclk(hclk, config.apb2_pre);
+    rcc_ensure!(max::PCLK.contains(&pclk1), RccError::PclkOutOfRange);
+    rcc_ensure!(max::PCLK.contains(&pclk2), RccError::PclkOutOfRange);
+    let pclk_tim_range = match config.voltage_range {
+        VoltageRange::RANGE2 => max::range2::PCLK_TIM,
+        _ => max::PCLK_TIM,
+    };
+    rcc_ensure!(
+        pclk_tim_range.contains(&pclk1_tim) && pclk_tim_range.contains(&pclk2_tim),
+        RccError::PclkTimOutOfRange
+    );
+
+    // Leave low-power run mode in case we are reconfiguring, it would limit HSI to 2MHz.
+    PWR.cr1().modify(|w| w.set_lpr(false));
+    while PWR.sr2().read().reglpf() {}
+
+    // Turn on the HSI
+    RCC.cr().modify(|w| w.set_hsion(true));
+    while !RCC.cr().read().hsirdy() {}
+
+    // Range 2 already needs a wait state at 16MHz. Use the highest latency needed in any mode
+    // during the setup, it is set for the final frequency below.
+    FLASH.acr().modify(|w| w.set_latency(Latency::WS4));
+    while FLASH.acr().read().latency() != Latency::WS4 {}
+
+    // Use the HSI clock as system clock during the actual clock setup. Also drop the bus
+    // prescalers of a previous configuration, so the core really runs at 16MHz.
+    RCC.cfgr().modify(|w| {
+        w.set_sw(Sysclk::HSI);
+        w.set_hpre(AHBPrescaler::DIV1);
+        w.set_ppre1(APBPrescaler::DIV1);
+        w.set_ppre2(APBPrescaler::DIV1);
+    });
+    while RCC.cfgr().read().sws() != Sysclk::HSI {}
+
+    // Change the voltage range while running from HSI16, which is allowed in both ranges.
+    if PWR.cr1().read().vos() != config.voltage_range {
+        PWR.cr1().modify(|w| w.set_vos(config.voltage_range));
+        while PWR.sr2().read().vosf() {}
+    }
+
+    // Configure HSE
+    match config.hse {
+        None => RCC.cr().modify(|w| w.set_hseon(false)),
+        Some(hse) => {
+            RCC.cr().modify(|w| w.set_hsebyp(hse.mode != HseMode::Oscillator));
+            RCC.cr().modify(|w| w.set_hseon(true));
+            if let Err(e) = wait_ready(
+                HSE_TIMEOUT_ITERATIONS,
+                || RCC.cr().read().hserdy(),
+                RccError::HseTimeout,
+            ) {
+                // Don't leave a half-started oscillator behind for a retry with another config.
+                RCC.cr().modify(|w| w.set_hseon(false));
+                return Err(e);
+            }
+            settle(config.hse_settle_us);
+        }
+    }
+
+    // Configure HSI48 if required, or turn it off in case a previous configuration enabled it.
+    let hsi48 = match config.hsi48 {
+        Some(hsi48_config) => Some(super::init_hsi48(hsi48_config)),
+        None => {
+            if RCC.apb1enr1().read().crsen() {
+                crate::pac::CRS.cr().modify(|w| {
+                    w.set_cen(false);
+                    w.set_autotrimen(false);
+                });
+            }
+            RCC.crrcr().modify(|w| w.set_hsi48on(false));
+            None
+        }
+    };
+
+    match config.pll {
+        None => {
+            // Turn off a PLL left running by a previous configuration.
+            RCC.cr().modify(|w| w.set_pllon(false));
+            while RCC.cr().read().pllrdy() {}
+            RCC.pllcfgr().modify(|w| {
+                w.set_pllpen(false);
+                w.set_pllqen(false);
+                w.set_pllren(false);
+            });
+        }
+        Some(pll_config) => {
             // Disable PLL before configuration
             RCC.cr().modify(|w| w.set_pllon(false));
             while RCC.cr().read().pllrdy() {}
 
-            let in_freq = src_freq / pll_config.prediv;
-            rcc_assert!(max::PLL_IN.contains(&in_freq));
-            let internal_freq = in_freq * pll_config.mul;
-
-            rcc_assert!(max::PLL_VCO.contains(&internal_freq));
-
             RCC.pllcfgr().write(|w| {
                 w.set_plln(pll_config.mul);
                 w.set_pllm(pll_config.prediv);
                 w.set_pllsrc(pll_config.source.into());
-            });
-
-            let pll_p_freq = pll_config.divp.map(|div_p| {
-                RCC.pllcfgr().modify(|w| {
+                if let Some(div_p) = pll_config.divp {
                     w.set_pllp(div_p);
                     w.set_pllpen(true);
-                });
-                let freq = internal_freq / div_p;
-                rcc_assert!(max::PLL_P.contains(&freq));
-                freq
-            });
-
-            let pll_q_freq = pll_config.divq.map(|div_q| {
-                RCC.pllcfgr().modify(|w| {
+                }
+                if let Some(div_q) = pll_config.divq {
                     w.set_pllq(div_q);
                     w.set_pllqen(true);
-                });
-                let freq = internal_freq / div_q;
-                rcc_assert!(max::PLL_Q.contains(&freq));
-                freq
-            });
-
-            let pll_r_freq = pll_config.divr.map(|div_r| {
-                RCC.pllcfgr().modify(|w| {
+                }
+                if let Some(div_r) = pll_config.divr {
                     w.set_pllr(div_r);
                     w.set_pllren(true);
-                });
-                let freq = internal_freq / div_r;
-                rcc_assert!(max::PLL_R.contains(&freq));
-                freq
+                }
             });
 
             // Enable the PLL
             RCC.cr().modify(|w| w.set_pllon(true));
-            while !RCC.cr().read().pllrdy() {}
-
-            PllFreq {
-                pll_p: pll_p_freq,
-                pll_q: pll_q_freq,
-                pll_r: pll_r_freq,
+            if let Err(e) = wait_ready(
+                PLL_TIMEOUT_ITERATIONS,
+                || RCC.cr().read().pllrdy(),
+                RccError::PllTimeout,
+            ) {
+                RCC.cr().modify(|w| w.set_pllon(false));
+                return Err(e);
             }
-        })
-        .unwrap_or_default();
-
-    let sys = match config.sys {
-        Sysclk::HSI => unwrap!(hsi),
-        Sysclk::HSE => unwrap!(hse),
-        Sysclk::PLL1_R => unwrap!(pll.pll_r),
-        _ => unreachable!(),
-    };
-
-    rcc_assert!(max::SYSCLK.contains(&sys));
-
-    // Calculate the AHB frequency (HCLK), among other things so we can calculate the correct flash read latency.
-    let hclk = sys / config.ahb_pre;
-    rcc_assert!(max::HCLK.contains(&hclk));
-
-    let (pclk1, pclk1_tim) = super::util::calc_pclk(hclk, config.apb1_pre);
-    let (pclk2, pclk2_tim) = super::util::calc_pclk(hclk, config.apb2_pre);
-    rcc_assert!(max::PCLK.contains(&pclk1));
-    rcc_assert!(max::PCLK.contains(&pclk2));
+            settle(config.pll_settle_us);
+
+            let src_freq = unwrap!(pll_src_freq);
+            debug!(
+                "rcc: pll in={} m={} n={} vco={} p={:?} q={:?} r={:?}",
+                src_freq,
+                prediv_factor(pll_config.prediv),
+                mul_factor(pll_config.mul),
+                pll_config.vco(src_freq),
+                pll_config.divp.map(divp_factor),
+                pll_config.divq.map(divq_factor),
+                pll_config.divr.map(divr_factor)
+            );
+        }
+    }
 
     // Configure Core Boost mode ([RM0440] p234 – inverted because setting r1mode to 0 enables boost mode!)
     if config.boost {
@@ -252,23 +502,31 @@ pub(crate) unsafe fn init(config: Config) {
         // Below:
         // 3. Adjust wait states according to new freq target
         // 4. Configure and switch to new frequency
+    } else {
+        // Back to range 1 normal mode, in case boost was enabled by a previous configuration.
+        // This is safe since we are running from HSI at this point.
+        PWR.cr5().modify(|w| w.set_r1mode(true));
     }
 
-    let latency = match (config.boost, hclk.0) {
-        (true, ..=34_000_000) => Latency::WS0,
-        (true, ..=68_000_000) => Latency::WS1,
-        (true, ..=102_000_000) => Latency::WS2,
-        (true, ..=136_000_000) => Latency::WS3,
-        (true, _) => Latency::WS4,
-
-        (false, ..=36_000_000) => Latency::WS0,
-        (false, ..=60_000_000) => Latency::WS1,
-        (false, ..=90_000_000) => Latency::WS2,
-        (false, ..=120_000_000) => Latency::WS3,
-        (false, _) => Latency::WS4,
+    let latency = match (config.voltage_range, config.boost, hclk.0) {
+        (VoltageRange::RANGE2, _, ..=12_000_000) => Latency::WS0,
+        (VoltageRange::RANGE2, _, ..=24_000_000) => Latency::WS1,
+        (VoltageRange::RANGE2, _, _) => Latency::WS2,
+
+        (_, true, ..=34_000_000) => Latency::WS0,
+        (_, true, ..=68_000_000) => Latency::WS1,
+        (_, true, ..=102_000_000) => Latency::WS2,
+        (_, true, ..=136_000_000) => Latency::WS3,
+        (_, true, _) => Latency::WS4,
+
+        (_, false, ..=30_000_000) => Latency::WS0,
+        (_, false, ..=60_000_000) => Latency::WS1,
+        (_, false, ..=90_000_000) => Latency::WS2,
+        (_, false, ..=120_000_000) => Latency::WS3,
+        (_, false, _) => Latency::WS4,
     };
 
-    // Configure flash read access latency based on boost mode and frequency (RM0440 p98)
+    // Configure flash read access latency based on boost mode and frequency (RM0440 p98, table 9)
     FLASH.acr().modify(|w| {
         w.set_latency(latency);
     });
@@ -295,12 +553,12 @@ pub(crate) unsafe fn init(config: Config) {
         RCC.cr().modify(|w| w.set_hsion(false));
     }
 
+    // Only enter low-power run now that the slower clock is actually in use.
     if config.low_power_run {
-        assert!(sys <= Hertz(2_000_000));
         PWR.cr1().modify(|w| w.set_lpr(true));
     }
 
-    let rtc = config.ls.init();
+    let rtc = config.ls.init_with_hse(hse);
 
     config.mux.init();
 
@@ -319,12 +577,123 @@ pub(crate) unsafe fn init(config: Config) {
         hsi: hsi,
         hse: hse,
         hsi48: hsi48,
+        lsi: config.ls.lsi.then_some(super::LSI_FREQ),
+        rtc: rtc,
+    );
+
+    debug!(
+        "rcc: sys={} (sw={}) hclk={} pclk1={} pclk1_tim={} pclk2={} pclk2_tim={} hsi48={:?} pll1_q={:?} clk48sel={}",
+        sys,
+        config.sys.to_bits(),
+        hclk,
+        pclk1,
+        pclk1_tim,
+        pclk2,
+        pclk2_tim,
+        hsi48,
+        pll.pll_q,
+        config.mux.clk48sel.to_bits()
+    );
+
+    Ok(())
+}
+
+/// Return the clock tree to its reset state.
+///
+/// This switches SYSCLK back to HSI16, disables the PLL and HSE, resets the bus prescalers,
+/// leaves boost and low-power run mode and restores the reset flash latency and PLL configuration.
+/// HSI48 and the backup-domain clocks are left untouched, but an RTC running from HSE stops.
+///
+/// Call this right before handing off to a bootloader that assumes the reset clock
+/// configuration.
+///
+/// # Safety
+///
+/// Running drivers keep using the clock frequencies they were configured with, so they must not
+/// be used afterwards.
+pub unsafe fn deinit() {
+    // Low-power run limits SYSCLK to 2MHz, leave it before switching to HSI16.
+    PWR.cr1().modify(|w| w.set_lpr(false));
+    while PWR.sr2().read().reglpf() {}
+
+    RCC.cr().modify(|w| w.set_hsion(true));
+    while !RCC.cr().read().hsirdy() {}
+
+    // Lower the frequency first, then the flash latency. Range 2 needs a wait state at 16MHz.
+    FLASH.acr().modify(|w| w.set_latency(Latency::WS4));
+    while FLASH.acr().read().latency() != Latency::WS4 {}
+
+    RCC.cfgr().modify(|w| {
+        w.set_sw(Sysclk::HSI);
+        w.set_hpre(AHBPrescaler::DIV1);
+        w.set_ppre1(APBPrescaler::DIV1);
+        w.set_ppre2(APBPrescaler::DIV1);
+    });
+    while RCC.cfgr().read().sws() != Sysclk::HSI {}
+
+    // Go back to range 1 before dropping the wait states.
+    PWR.cr1().modify(|w| w.set_vos(VoltageRange::RANGE1));
+    while PWR.sr2().read().vosf() {}
+
+    FLASH.acr().modify(|w| w.set_latency(Latency::WS0));
+    while FLASH.acr().read().latency() != Latency::WS0 {}
+
+    // Back to Range 1 normal mode (R1MODE is set after reset).
+    PWR.cr5().modify(|w| w.set_r1mode(true));
+
+    RCC.cr().modify(|w| w.set_pllon(false));
+    while RCC.cr().read().pllrdy() {}
+    // PLLCFGR resets to PLLN = 16, not 0.
+    RCC.pllcfgr().write_value(crate::pac::rcc::regs::Pllcfgr(0x0000_1000));
+
+    RCC.cr().modify(|w| {
+        w.set_hseon(false);
+        w.set_hsebyp(false);
+    });
+
+    set_hsi_clocks(None);
+}
+
+/// Record the clocks while SYSCLK runs from HSI16 with the bus prescalers at 1, after `deinit` or
+/// after the clock setup of `reinit` timed out. `hse` is the HSE frequency if it is still running.
+///
+/// The PLL and RTC frequencies are taken from the previous [`Clocks`](super::Clocks), so this may
+/// only be called once `init` has set them.
+pub(crate) unsafe fn set_hsi_clocks(hse: Option<Hertz>) {
+    let old = *super::get_freqs();
+    // A timeout on HSE leaves the PLL of the previous configuration running.
+    let pll_on = RCC.cr().read().pllrdy();
+    let hsi48 = match RCC.crrcr().read().hsi48rdy() {
+        true => Some(super::HSI48_FREQ),
+        false => None,
+    };
+    let rtc = match RCC.bdcr().read().rtcsel() {
+        super::RtcClockSource::HSE => hse.map(|hse| hse / 32u32),
+        _ => old.rtc.to_hertz(),
+    };
+
+    set_clocks!(
+        sys: Some(HSI_FREQ),
+        hclk1: Some(HSI_FREQ),
+        hclk2: Some(HSI_FREQ),
+        hclk3: Some(HSI_FREQ),
+        pclk1: Some(HSI_FREQ),
+        pclk1_tim: Some(HSI_FREQ),
+        pclk2: Some(HSI_FREQ),
+        pclk2_tim: Some(HSI_FREQ),
+        pll1_p: old.pll1_p.to_hertz().filter(|_| pll_on),
+        pll1_q: old.pll1_q.to_hertz().filter(|_| pll_on),
+        pll1_r: old.pll1_r.to_hertz().filter(|_| pll_on),
+        hsi: Some(HSI_FREQ),
+        hse: hse,
+        hsi48: hsi48,
+        lsi: super::lsi_ready().then_some(super::LSI_FREQ),
         rtc: rtc,
     );
 }
 
 /// Acceptable Frequency Ranges
-/// Currently assuming voltage scaling range 1 boost mode.
+/// Top-level limits are for voltage scaling range 1 boost mode, see `range2` for range 2.
 /// Where not specified in the generic G4 reference manual (RM0440), values taken from the STM32G474 datasheet.
 /// If acceptable ranges for other G4-family chips differ, make additional max modules gated behind cfg attrs.
 mod max {
@@ -341,18 +710,192 @@ mod max {
     /// SYSCLK Frequency Range (RM0440 p282)
     pub(crate) const SYSCLK: RangeInclusive<Hertz> = Hertz(0)..=Hertz(170_000_000);
 
+    /// SYSCLK Frequency Range in range 1 normal mode, without boost (RM0440, Dynamic voltage scaling management)
+    pub(crate) const SYSCLK_NO_BOOST: RangeInclusive<Hertz> = Hertz(0)..=Hertz(150_000_000);
+
     /// PLL Output Frequency Range (RM0440 p281, STM32G474 Datasheet p123, Table 46)
     pub(crate) const PCLK: RangeInclusive<Hertz> = Hertz(8)..=Hertz(170_000_000);
 
+    /// APB timer clock Frequency Range (STM32G474 Datasheet)
+    pub(crate) const PCLK_TIM: RangeInclusive<Hertz> = Hertz(0)..=Hertz(170_000_000);
+
     /// HCLK (AHB) Clock Frequency Range (STM32G474 Datasheet)
     pub(crate) const HCLK: RangeInclusive<Hertz> = Hertz(0)..=Hertz(170_000_000);
 
+    /// Maximum HCLK in low-power run mode (RM0440, Low-power run mode)
+    pub(crate) const HCLK_LOW_POWER_RUN: Hertz = Hertz(2_000_000);
+
     /// PLL Source Frequency Range (STM32G474 Datasheet p123, Table 46)
-    pub(crate) const PLL_IN: RangeInclusive<Hertz> = Hertz(2_660_000)..=Hertz(16_000_000);
+    pub(crate) const PLL_IN: RangeInclusive<Hertz> = Hertz::khz(2_660)..=Hertz::mhz(16);
 
     /// PLL VCO (internal) Frequency Range (STM32G474 Datasheet p123, Table 46)
     pub(crate) const PLL_VCO: RangeInclusive<Hertz> = Hertz(96_000_000)..=Hertz(344_000_000);
     pub(crate) const PLL_P: RangeInclusive<Hertz> = Hertz(2_064_500)..=Hertz(170_000_000);
     pub(crate) const PLL_Q: RangeInclusive<Hertz> = Hertz(8_000_000)..=Hertz(170_000_000);
     pub(crate) const PLL_R: RangeInclusive<Hertz> = Hertz(8_000_000)..=Hertz(170_000_000);
+
+    /// Limits in voltage scaling range 2 (STM32G474 Datasheet, Table 46 and General operating conditions)
+    pub(crate) mod range2 {
+        use core::ops::RangeInclusive;
+
+        use crate::time::Hertz;
+
+        pub(crate) const SYSCLK: RangeInclusive<Hertz> = Hertz(0)..=Hertz(26_000_000);
+        pub(crate) const PCLK_TIM: RangeInclusive<Hertz> = Hertz(0)..=Hertz(26_000_000);
+        pub(crate) const PLL_VCO: RangeInclusive<Hertz> = Hertz(96_000_000)..=Hertz(128_000_000);
+        pub(crate) const PLL_P: RangeInclusive<Hertz> = Hertz(2_064_500)..=Hertz(26_000_000);
+        pub(crate) const PLL_Q: RangeInclusive<Hertz> = Hertz(8_000_000)..=Hertz(26_000_000);
+        pub(crate) const PLL_R: RangeInclusive<Hertz> = Hertz(8_000_000)..=Hertz(26_000_000);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pll_factors_match_hertz_ops() {
+        let freq = Hertz(3_603_600_000);
+        for bits in 0..=15 {
+            let div = PllPreDiv::from_bits(bits);
+            assert_eq!(freq / div, Hertz(freq.0 / prediv_factor(div)));
+        }
+        for bits in 8..=127 {
+            let mul = PllMul::from_bits(bits);
+            assert_eq!(Hertz(1) * mul, Hertz(mul_factor(mul)));
+        }
+        for bits in 2..=31 {
+            let div = PllPDiv::from_bits(bits);
+            assert_eq!(freq / div, Hertz(freq.0 / divp_factor(div)));
+        }
+        for bits in 0..=3 {
+            assert_eq!(
+                freq / PllQDiv::from_bits(bits),
+                Hertz(freq.0 / divq_factor(PllQDiv::from_bits(bits)))
+            );
+            assert_eq!(
+                freq / PllRDiv::from_bits(bits),
+                Hertz(freq.0 / divr_factor(PllRDiv::from_bits(bits)))
+            );
+        }
+    }
+
+    #[test]
+    fn timer_clock_doubles_only_when_divided() {
+        use crate::rcc::util::calc_pclk;
+
+        let hclk = Hertz(170_000_000);
+        assert_eq!(
+            calc_pclk(hclk, APBPrescaler::DIV1),
+            (Hertz(170_000_000), Hertz(170_000_000))
+        );
+        assert_eq!(
+            calc_pclk(hclk, APBPrescaler::DIV2),
+            (Hertz(85_000_000), Hertz(170_000_000))
+        );
+        assert_eq!(
+            calc_pclk(hclk, APBPrescaler::DIV4),
+            (Hertz(42_500_000), Hertz(85_000_000))
+        );
+        assert_eq!(
+            calc_pclk(hclk, APBPrescaler::DIV16),
+            (Hertz(10_625_000), Hertz(21_250_000))
+        );
+    }
+
+    #[test]
+    fn ahb_prescaler_skips_div32() {
+        let sys = Hertz(170_000_000);
+        let expected = [
+            (AHBPrescaler::DIV1, 1),
+            (AHBPrescaler::DIV2, 2),
+            (AHBPrescaler::DIV4, 4),
+            (AHBPrescaler::DIV8, 8),
+            (AHBPrescaler::DIV16, 16),
+            (AHBPrescaler::DIV64, 64),
+            (AHBPrescaler::DIV128, 128),
+            (AHBPrescaler::DIV256, 256),
+            (AHBPrescaler::DIV512, 512),
+        ];
+        for (pre, div) in expected {
+            assert_eq!(sys / pre, Hertz(sys.0 / div));
+        }
+    }
+
+    #[test]
+    fn pll_input_bounds() {
+        let pll = Pll {
+            source: PllSource::HSE,
+            prediv: PllPreDiv::DIV1,
+            mul: PllMul::MUL64,
+            divp: None,
+            divq: None,
+            divr: Some(PllRDiv::DIV2),
+        };
+        assert!(pll.check(Hertz::khz(2_660), VoltageRange::RANGE1).is_ok());
+        assert_eq!(
+            pll.check(Hertz::khz(2_659), VoltageRange::RANGE1).err(),
+            Some(RccError::VcoInputOutOfRange)
+        );
+
+        let pll = Pll {
+            mul: PllMul::MUL8,
+            ..pll
+        };
+        assert!(pll.check(Hertz::mhz(16), VoltageRange::RANGE1).is_ok());
+        assert_eq!(
+            pll.check(Hertz::khz(16_001), VoltageRange::RANGE1).err(),
+            Some(RccError::VcoInputOutOfRange)
+        );
+    }
+
+    #[test]
+    fn pll_check() {
+        let mut pll = Pll {
+            source: PllSource::HSE,
+            prediv: PllPreDiv::DIV6,
+            mul: PllMul::MUL85,
+            divp: None,
+            divq: Some(PllQDiv::DIV8),
+            divr: Some(PllRDiv::DIV2),
+        };
+        let freq = pll.check(Hertz::mhz(24), VoltageRange::RANGE1).unwrap();
+        assert_eq!(freq.pll_q, Some(Hertz(42_500_000)));
+        assert_eq!(freq.pll_r, Some(Hertz::mhz(170)));
+
+        // 8MHz * 85 overshoots the VCO range.
+        pll.prediv = PllPreDiv::DIV3;
+        assert_eq!(
+            pll.check(Hertz::mhz(24), VoltageRange::RANGE1).err(),
+            Some(RccError::VcoOutputOutOfRange)
+        );
+
+        // 1.5MHz is below the PLL input range.
+        pll.prediv = PllPreDiv::DIV16;
+        assert_eq!(
+            pll.check(Hertz::mhz(24), VoltageRange::RANGE1).err(),
+            Some(RccError::VcoInputOutOfRange)
+        );
+
+        // 344MHz is a valid VCO frequency, but 172MHz on R is too fast.
+        pll.prediv = PllPreDiv::DIV6;
+        pll.mul = PllMul::MUL86;
+        assert_eq!(
+            pll.check(Hertz::mhz(24), VoltageRange::RANGE1).err(),
+            Some(RccError::PllROutOfRange)
+        );
+
+        // 24MHz out of a 96MHz VCO is fine in range 2, 144MHz is not.
+        pll.mul = PllMul::MUL24;
+        pll.divr = Some(PllRDiv::DIV4);
+        assert_eq!(
+            pll.check(Hertz::mhz(24), VoltageRange::RANGE2).unwrap().pll_r,
+            Some(Hertz::mhz(24))
+        );
+        pll.mul = PllMul::MUL36;
+        assert_eq!(
+            pll.check(Hertz::mhz(24), VoltageRange::RANGE2).err(),
+            Some(RccError::VcoOutputOutOfRange)
+        );
+    }
 }
@@ -36,6 +36,129 @@ use stm32_metapac::RCC;
 pub use crate::_generated::{mux, Clocks};
 use crate::time::Hertz;
 
+/// Error returned when the requested clock configuration cannot be applied.
+///
+/// Currently only the STM32G4 clock setup reports errors, other families still panic on an
+/// invalid configuration. An invalid configuration is rejected before any register is written. If
+/// HSE or the PLL fail to start, the system keeps running from the HSI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum RccError {
+    /// A clock used as a source (for the PLL or SYSCLK) is not enabled.
+    ClockNotEnabled,
+    /// HSE frequency is out of the supported range.
+    HseOutOfRange,
+    /// PLL input frequency (after the pre-divider) is out of range.
+    VcoInputOutOfRange,
+    /// PLL VCO frequency is out of range.
+    VcoOutputOutOfRange,
+    /// PLL P output frequency is out of range.
+    PllPOutOfRange,
+    /// PLL Q output frequency is out of range.
+    PllQOutOfRange,
+    /// PLL R output frequency is out of range.
+    PllROutOfRange,
+    /// SYSCLK frequency is out of range.
+    SysclkOutOfRange,
+    /// SYSCLK frequency is too high without boost mode.
+    BoostRequired,
+    /// Boost mode is only available in voltage range 1.
+    BoostUnavailable,
+    /// AHB frequency is out of range.
+    HclkOutOfRange,
+    /// APB frequency is out of range.
+    PclkOutOfRange,
+    /// APB timer clock frequency is out of range.
+    PclkTimOutOfRange,
+    /// Low-power run mode was requested with HCLK above 2MHz.
+    LowPowerRunTooFast,
+    /// HSE did not become ready in time, the crystal or external clock may be missing.
+    HseTimeout,
+    /// PLL did not lock in time.
+    PllTimeout,
+}
+
+/// Reconfigure the clocks at runtime.
+///
+/// SYSCLK is switched to HSI16 first and the new configuration is then applied the same way as at
+/// startup, so the flash latency is always changed while running at 16MHz whether the new clock
+/// is faster or slower. Boost and low-power run mode are entered or left as needed.
+///
+/// The stored [`Clocks`] and the time driver are updated. Other drivers compute their clock
+/// dividers when they are created, so they must be re-created or reconfigured afterwards.
+///
+/// The configuration is checked before any register is written, so on an invalid one the
+/// previous clocks are left running untouched. If HSE or the PLL then fail to start
+/// ([`RccError::HseTimeout`], [`RccError::PllTimeout`]), the oscillator that timed out is turned
+/// off again and the chip keeps running from HSI16 with all bus prescalers at 1. [`Clocks`] and
+/// the time driver are updated to that state as well, so embassy-time stays accurate, but the
+/// other drivers must be reconfigured just like after a successful call.
+#[cfg(stm32g4)]
+pub fn reinit(config: Config, _rcc: &mut crate::Peri<'_, crate::peripherals::RCC>) -> Result<(), RccError> {
+    critical_section::with(|_cs| unsafe {
+        let res = try_init(config);
+        if let Err(RccError::HseTimeout | RccError::PllTimeout) = res {
+            let hse = config.hse.map(|hse| hse.freq).filter(|_| RCC.cr().read().hserdy());
+            set_hsi_clocks(hse);
+        }
+
+        #[cfg(feature = "_time-driver")]
+        crate::time_driver::update_prescaler(_cs);
+
+        res?;
+
+        #[cfg(feature = "low-power")]
+        set_resume_config(config);
+
+        Ok(())
+    })
+}
+
+#[cfg(not(stm32g4))]
+pub(crate) unsafe fn try_init(config: Config) -> Result<(), RccError> {
+    init(config);
+    Ok(())
+}
+
+#[cfg(all(feature = "low-power", any(stm32g4, stm32wl)))]
+/// Configuration applied by the last `init`, replayed when waking up from STOP mode.
+static mut RESUME_CONFIG: Option<Config> = None;
+
+#[cfg(all(feature = "low-power", any(stm32g4, stm32wl)))]
+/// Safety: Sets a mutable global, must be called within a critical section.
+pub(crate) unsafe fn set_resume_config(config: Config) {
+    RESUME_CONFIG = Some(config);
+}
+
+/// Restore the clock configuration after waking up from STOP mode.
+///
+/// The chip wakes up from STOP running from HSI (or MSI) with the PLL and HSE off. If that is the
+/// case, this applies the configuration from the last `init` again: oscillators and PLL are
+/// restarted, the flash latency is restored and SYSCLK is switched back. Otherwise it does
+/// nothing, so running peripherals are not disturbed.
+///
+/// The low-power [`Executor`](crate::low_power::Executor) calls this on the first interrupt after
+/// it entered STOP, before resuming the time driver, so the timer ticks at the rate embassy-time
+/// expects again. Only call it yourself when entering STOP mode manually.
+///
+/// Only available on families whose clock init can safely run again on a running system.
+#[cfg(all(feature = "low-power", any(stm32g4, stm32wl)))]
+pub fn resume_after_stop() {
+    critical_section::with(|_| unsafe {
+        let Some(config) = core::ptr::addr_of!(RESUME_CONFIG).read() else {
+            return;
+        };
+
+        let lost = RCC.cfgr().read().sws() != config.sys
+            || (config.pll.is_some() && !RCC.cr().read().pllrdy())
+            || (config.hse.is_some() && !RCC.cr().read().hserdy());
+        if lost {
+            unwrap!(try_init(config));
+        }
+    })
+}
+
 #[cfg(feature = "low-power")]
 /// Must be written within a critical section
 ///
@@ -49,11 +172,14 @@ pub(crate) static mut REFCOUNT_STOP1: u32 = 0;
 pub(crate) static mut REFCOUNT_STOP2: u32 = 0;
 
 #[cfg(not(feature = "_dual-core"))]
-/// Frozen clock frequencies
+/// Current clock frequencies
 ///
-/// The existence of this value indicates that the clock configuration can no longer be changed
+/// Set by `init`, and updated by `reinit` on chips that support it.
 static mut CLOCK_FREQS: MaybeUninit<Clocks> = MaybeUninit::uninit();
 
+/// Set once the clock frequencies have been stored, checked by [`clocks`].
+static CLOCK_FREQS_SET: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+
 #[cfg(feature = "_dual-core")]
 static CLOCK_FREQS_PTR: core::sync::atomic::AtomicPtr<MaybeUninit<Clocks>> =
     core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
@@ -63,6 +189,12 @@ pub(crate) fn set_freqs_ptr(freqs: *mut MaybeUninit<Clocks>) {
     CLOCK_FREQS_PTR.store(freqs, core::sync::atomic::Ordering::SeqCst);
 }
 
+#[cfg(feature = "_dual-core")]
+/// Marks the clock frequencies shared by the other core as valid.
+pub(crate) fn set_freqs_valid() {
+    CLOCK_FREQS_SET.store(true, core::sync::atomic::Ordering::SeqCst);
+}
+
 #[cfg(not(feature = "_dual-core"))]
 /// Sets the clock frequencies
 ///
@@ -70,6 +202,7 @@ pub(crate) fn set_freqs_ptr(freqs: *mut MaybeUninit<Clocks>) {
 pub(crate) unsafe fn set_freqs(freqs: Clocks) {
     debug!("rcc: {:?}", freqs);
     CLOCK_FREQS = MaybeUninit::new(freqs);
+    CLOCK_FREQS_SET.store(true, core::sync::atomic::Ordering::SeqCst);
 }
 
 #[cfg(feature = "_dual-core")]
@@ -81,6 +214,7 @@ pub(crate) unsafe fn set_freqs(freqs: Clocks) {
     CLOCK_FREQS_PTR
         .load(core::sync::atomic::Ordering::SeqCst)
         .write(MaybeUninit::new(freqs));
+    CLOCK_FREQS_SET.store(true, core::sync::atomic::Ordering::SeqCst);
 }
 
 #[cfg(not(feature = "_dual-core"))]
@@ -321,6 +455,22 @@ mod util {
     }
 }
 
+/// Get the current clock frequencies, as set by `init` and updated by `reinit`.
+///
+/// This can be called from any context, including interrupt handlers.
+///
+/// # Panics
+///
+/// Panics if the clocks have not been initialized yet.
+pub fn clocks() -> Clocks {
+    assert!(
+        CLOCK_FREQS_SET.load(core::sync::atomic::Ordering::SeqCst),
+        "clocks() called before init"
+    );
+    // Safety: the frequencies are set, and `reinit` only updates them within a critical section.
+    critical_section::with(|_| unsafe { *get_freqs() })
+}
+
 /// Get the kernel clock frequency of the peripheral `T`.
 ///
 /// # Panics
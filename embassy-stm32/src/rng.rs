@@ -52,6 +52,19 @@ impl<'d, T: Instance> Rng<'d, T> {
         inner: Peri<'d, T>,
         _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
     ) -> Self {
+        // The RNG kernel clock comes from the 48MHz domain, a missing or wrong clock only shows
+        // up later as seed errors. frequency() will panic if not enabled.
+        #[cfg(stm32g4)]
+        {
+            let freq = T::frequency();
+            if freq.0.abs_diff(48_000_000) > 120_000 {
+                panic!(
+                    "RNG clock should be 48Mhz but is {} Hz. Please double-check your RCC settings.",
+                    freq.0
+                )
+            }
+        }
+
         rcc::enable_and_reset::<T>();
         let mut random = Self { _inner: inner };
         random.reset();
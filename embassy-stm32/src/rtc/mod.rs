@@ -161,6 +161,11 @@ impl Rtc {
             _private: (),
         };
 
+        // The prescalers below assume the RTC clock is running at its nominal frequency.
+        if crate::rcc::rtc_clock_source() == crate::rcc::RtcClockSource::LSI {
+            assert!(crate::rcc::lsi_ready(), "RTC clock source is LSI, but LSI is not ready");
+        }
+
         let frequency = Self::frequency();
         let async_psc = ((frequency.0 / rtc_config.frequency.0) - 1) as u8;
         let sync_psc = (rtc_config.frequency.0 - 1) as u16;
@@ -627,12 +627,34 @@ impl<'d> Spi<'d, Async> {
         peri: Peri<'d, T>,
         tx_dma: Peri<'d, impl TxDma<T>>,
         rx_dma: Peri<'d, impl RxDma<T>>,
+    ) -> Self {
+        Self::new_subghz_with_frequency(peri, tx_dma, rx_dma, Hertz(16_000_000))
+    }
+
+    #[cfg(stm32wl)]
+    /// Like [`Spi::new_subghz`], but with the SUBGHZSPI clock limited to `max_freq`.
+    ///
+    /// The clock uses the fastest PCLK3 divider that does not exceed `max_freq`, and it never
+    /// exceeds PCLK3 / 2 or 16MHz. Use [`Spi::get_current_config`] to read back the frequency
+    /// actually in use.
+    ///
+    /// Panics if `max_freq` is 0 or below PCLK3 / 256.
+    pub fn new_subghz_with_frequency<T: Instance>(
+        peri: Peri<'d, T>,
+        tx_dma: Peri<'d, impl TxDma<T>>,
+        rx_dma: Peri<'d, impl RxDma<T>>,
+        max_freq: Hertz,
     ) -> Self {
         // see RM0453 rev 1 section 7.2.13 page 291
         // The SUBGHZSPI_SCK frequency is obtained by PCLK3 divided by two.
         // The SUBGHZSPI_SCK clock maximum speed must not exceed 16 MHz.
         let pclk3_freq = <crate::peripherals::SUBGHZSPI as SealedRccPeripheral>::frequency().0;
-        let freq = Hertz(core::cmp::min(pclk3_freq / 2, 16_000_000));
+        assert!(max_freq.0 > 0, "SUBGHZSPI frequency must not be 0");
+        let min_div = pclk3_freq.div_ceil(core::cmp::min(max_freq.0, 16_000_000)).max(2);
+        assert!(min_div <= 256, "SUBGHZSPI frequency is below PCLK3 / 256");
+        // The baud rate dividers are powers of two. Pass the exact divided clock so that
+        // `compute_baud_rate` selects this divider instead of rounding to the nearest one.
+        let freq = Hertz(pclk3_freq / min_div.next_power_of_two());
         let mut config = Config::default();
         config.mode = MODE_0;
         config.bit_order = BitOrder::MsbFirst;
@@ -231,18 +231,10 @@ impl RtcDriver {
 
         rcc::enable_and_reset_with_cs::<T>(cs);
 
-        let timer_freq = T::frequency();
-
         r.cr1().modify(|w| w.set_cen(false));
         r.cnt().write(|w| w.set_cnt(0));
 
-        let psc = timer_freq.0 / TICK_HZ as u32 - 1;
-        let psc: u16 = match psc.try_into() {
-            Err(_) => panic!("psc division overflow: {}", psc),
-            Ok(n) => n,
-        };
-
-        r.psc().write_value(psc);
+        r.psc().write_value(Self::prescaler());
         r.arr().write(|w| w.set_arr(u16::MAX));
 
         // Set URS, generate update and clear URS
@@ -265,6 +257,32 @@ impl RtcDriver {
         r.cr1().modify(|w| w.set_cen(true));
     }
 
+    fn prescaler() -> u16 {
+        let timer_freq = T::frequency();
+
+        let psc = timer_freq.0 / TICK_HZ as u32 - 1;
+        match psc.try_into() {
+            Err(_) => panic!("psc division overflow: {}", psc),
+            Ok(n) => n,
+        }
+    }
+
+    /// Reprogram the prescaler after the timer kernel clock changed, keeping the current count.
+    #[cfg(stm32g4)]
+    fn update_prescaler(&self, _cs: critical_section::CriticalSection) {
+        let r = regs_gp16();
+
+        let cnt = r.cnt().read().cnt();
+        r.psc().write_value(Self::prescaler());
+
+        // The prescaler is only loaded on an update event, which also clears the counter.
+        // URS keeps the update from counting as an overflow.
+        r.cr1().modify(|w| w.set_urs(vals::Urs::COUNTER_ONLY));
+        r.egr().write(|w| w.set_ug(true));
+        r.cr1().modify(|w| w.set_urs(vals::Urs::ANY_EVENT));
+        r.cnt().write(|w| w.set_cnt(cnt));
+    }
+
     fn on_interrupt(&self) {
         let r = regs_gp16();
 
@@ -521,3 +539,8 @@ pub(crate) fn get_driver() -> &'static RtcDriver {
 pub(crate) fn init(cs: CriticalSection) {
     DRIVER.init(cs)
 }
+
+#[cfg(stm32g4)]
+pub(crate) fn update_prescaler(cs: CriticalSection) {
+    DRIVER.update_prescaler(cs)
+}
@@ -71,6 +71,8 @@ impl<'d, T: Instance> IndependentWatchdog<'d, T> {
     /// Unleash (start) the watchdog.
     pub fn unleash(&mut self) {
         T::regs().kr().write(|w| w.set_key(Key::START));
+        // Starting the IWDG also starts LSI, the timeout only counts once it is stable.
+        while !crate::rcc::lsi_ready() {}
     }
 
     /// Pet (reload, refresh) the watchdog.
@@ -28,6 +28,7 @@ async fn main(_spawner: Spawner) {
         });
         config.rcc.mux.adc12sel = mux::Adcsel::SYS;
         config.rcc.sys = Sysclk::PLL1_R;
+        config.rcc.boost = true;
     }
     let p = embassy_stm32::init(config);
 
@@ -33,6 +33,7 @@ async fn main(_spawner: Spawner) {
         });
         config.rcc.mux.fdcansel = mux::Fdcansel::PLL1_Q;
         config.rcc.sys = Sysclk::PLL1_R;
+        config.rcc.boost = true;
     }
     let peripherals = embassy_stm32::init(config);
 
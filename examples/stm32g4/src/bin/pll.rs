@@ -23,6 +23,7 @@ async fn main(_spawner: Spawner) {
             divr: Some(PllRDiv::DIV2),
         });
         config.rcc.sys = Sysclk::PLL1_R;
+        config.rcc.boost = true;
     }
     let _p = embassy_stm32::init(config);
     info!("Hello World!");
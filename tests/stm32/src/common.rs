@@ -502,6 +502,7 @@ pub fn config() -> Config {
         });
         config.rcc.mux.fdcansel = mux::Fdcansel::PLL1_Q;
         config.rcc.sys = Sysclk::PLL1_R;
+        config.rcc.boost = true;
     }
 
     #[cfg(any(feature = "stm32h755zi", feature = "stm32h753zi"))]